@@ -1,7 +1,3 @@
-fn main() {
-    println!("Hello, world!");
-}
-
 // Mandelbrot set
 // The Mandelbrot set is defined as the set of complex numbers c for which z does not fly out to infinity.
 
@@ -21,27 +17,97 @@ fn main() {
 //     }
 // }
 
-// Using the num crate on crates.io allows us to use complex number types. We import it into Cargo.toml
+// We used to pull in the `num` crate on crates.io for its generic Complex<T>
+// type, but every caller in this crate only ever wants f64 components, so we
+// keep our own small, concrete Complex type instead and implement just the
+// operators the Mandelbrot code needs. See the `impl`s below for those.
+
+/// A complex number, with `re` and `im` (real and imaginary) components.
+///
+/// Complex is a plain Rust structure type (or struct), defined like this:
+/// struct Complex {
+//     // Real portion of the complex number
+//     re: f64,
+//
+//     // Imaginary portion of the complex number
+//     im: f64,
+// }
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Complex {
+    re: f64,
+    im: f64,
+}
+
+impl Complex {
+    /// Returns the square of `self`'s distance from the origin.
+    fn norm_sqr(&self) -> f64 {
+        self.re * self.re + self.im * self.im
+    }
+
+    /// Returns `self`'s distance from the origin.
+    fn norm(&self) -> f64 {
+        self.norm_sqr().sqrt()
+    }
+
+    /// Returns `self`'s angle from the positive real axis, in radians.
+    #[allow(dead_code)]
+    fn arg(&self) -> f64 {
+        self.im.atan2(self.re)
+    }
+
+    /// Constructs a Complex number from polar coordinates: a distance `r`
+    /// from the origin and an angle `theta` from the positive real axis.
+    #[allow(dead_code)]
+    fn from_polar(r: f64, theta: f64) -> Complex {
+        Complex { re: r * theta.cos(), im: r * theta.sin() }
+    }
+
+    /// Returns `self`'s polar coordinates, as an `(r, theta)` pair.
+    #[allow(dead_code)]
+    fn to_polar(self) -> (f64, f64) {
+        (self.norm(), self.arg())
+    }
+}
+
+impl std::ops::Add for Complex {
+    type Output = Complex;
+    fn add(self, rhs: Complex) -> Complex {
+        Complex { re: self.re + rhs.re, im: self.im + rhs.im }
+    }
+}
+
+impl std::ops::Sub for Complex {
+    type Output = Complex;
+    fn sub(self, rhs: Complex) -> Complex {
+        Complex { re: self.re - rhs.re, im: self.im - rhs.im }
+    }
+}
 
-// Updated version using Complex numbers
-extern crate num;
-use num::Complex;
+impl std::ops::Mul for Complex {
+    type Output = Complex;
+    fn mul(self, rhs: Complex) -> Complex {
+        Complex {
+            re: self.re * rhs.re - self.im * rhs.im,
+            im: self.re * rhs.im + self.im * rhs.re,
+        }
+    }
+}
+
+impl std::ops::Neg for Complex {
+    type Output = Complex;
+    fn neg(self) -> Complex {
+        Complex { re: -self.re, im: -self.im }
+    }
+}
 
 #[allow(dead_code)]
-fn complex_square_add_loop(c: Complex<f64>) {
-    // re: 0.0... is the way we write complex zero using the num crate's Complex type. Complex is a Rust structure type (or struct), defined like this:
-    // struct Complex<T> {
-        // Real portion of the complex number
-        // re: T,
-
-        // Imaginary portion of the complex number
-        // im: T
-    // }
-    // The preceding code defines a struct name Complex, with two fields, re and im. Complex is a generic structure. We can read the <T> as "for any type T". The Complex value for re and im a f64 values as we declared it in the function definition up top.
+fn complex_square_add_loop(c: Complex) {
+    // The preceding definition declares a struct named Complex, with two
+    // fields, re and im, both f64.
     let mut z = Complex { re: 0.0, im: 0.0};
     loop {
         // Using 'z' is traditional for complex numbers
-        // The num create arranges for *, + and other operators to work on Complex values, it allows the function to operate on the complex plane, not just along the real number line.
+        // The operator impls above let *, + and other operators work on Complex values, so this function can operate on the complex plane, not just along the real number line.
         z = z * z + c;
     }
 }
@@ -57,7 +123,6 @@ fn complex_square_add_loop(c: Complex<f64>) {
 /// origin. If `c` seems to be a member (more precisely, if we reached the
 /// iteration limit without being able to prove that `c` is not a member),
 /// return `None`.
-
 // This function takes the complex number c that we want to test for membership in the Mandelbrot set, and a limit on the number of iterations to try before giving up and declaring c to probably be a member.
 // The function;s return value is an Option<u32>. An Option is an enumerated type, often called an enum because its definition enumerates several variants that a value of this type could be:
 // For any type T, a value of type Option<T> is either Some(v), where v is a value of type T, or None, indicating no T value is available.
@@ -66,7 +131,7 @@ fn complex_square_add_loop(c: Complex<f64>) {
 //     None,
 //     Some(T)
 // }
-fn escape_time(c: Complex<f64>, limit: u32) -> Option<u32> {
+fn escape_time(c: Complex, limit: u32) -> Option<u32> {
     let mut z = Complex { re: 0.0, im: 0.0 };
     // This for loop iterates over the range of integers starting with 0 and up to (but not including) limit.
     for i in 0..limit {
@@ -78,4 +143,586 @@ fn escape_time(c: Complex<f64>, limit: u32) -> Option<u32> {
     }
 
     None
+}
+
+/// Like `escape_time`, but returns a continuous (fractional) escape count
+/// instead of a raw iteration count.
+///
+/// `escape_time`'s `Some(i)` jumps by whole iterations from one pixel to the
+/// next, which shows up in a rendered image as visible rings of banding. To
+/// smooth that out, we keep iterating a little past the bailout, using a
+/// larger bailout radius (16 rather than 2, i.e. 256 rather than 4 for
+/// `norm_sqr`) so the extra iterations have room to work with, and then
+/// interpolate between iteration counts using how far past the radius `z`
+/// actually landed. The result, `mu`, is the continuous escape value; as
+/// with `escape_time`, `None` means `c` appears to be a member of the set.
+fn escape_time_smooth(c: Complex, limit: u32) -> Option<f64> {
+    let mut z = Complex { re: 0.0, im: 0.0 };
+
+    for i in 0..limit {
+        z = z * z + c;
+        if z.norm_sqr() > 256.0 {
+            let mu = i as f64 + 1.0 - (z.norm().ln() / 2f64.ln()).ln() / 2f64.ln();
+            return Some(mu);
+        }
+    }
+
+    None
+}
+
+/// Map a continuous escape value, as returned by `escape_time_smooth`, to an
+/// RGB color using a cosine palette: each channel is a phase-shifted cosine
+/// of `mu`, so the three channels sweep in and out of phase with each other
+/// as `mu` grows, giving a smooth, repeating gradient with no hard edges.
+fn color(mu: f64) -> [u8; 3] {
+    let t = mu * 0.05;
+    let channel = |phase: f64| -> u8 {
+        (255.0 * (0.5 + 0.5 * (2.0 * std::f64::consts::PI * t + phase).cos())) as u8
+    };
+
+    [channel(0.0), channel(2.0), channel(4.0)]
+}
+
+// Now that we can test a single point for membership, we need a way to turn that
+// into an actual picture. The `image` crate on crates.io gives us a PNG encoder.
+extern crate image;
+
+use image::ColorType;
+use image::png::PNGEncoder;
+use std::fs::File;
+
+/// Parse the string `s` as a coordinate pair, like `"400x600"` or
+/// `"1.0,-0.5"`.
+///
+/// `separator` must be the character that separates the two coordinates. If
+/// `s` has the proper form, return `Some((x, y))`. If it doesn't parse
+/// correctly, return `None`.
+fn parse_pair<T: std::str::FromStr>(s: &str, separator: char) -> Option<(T, T)> {
+    match s.find(separator) {
+        None => None,
+        Some(index) => {
+            match (T::from_str(&s[..index]), T::from_str(&s[index + 1..])) {
+                (Ok(l), Ok(r)) => Some((l, r)),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Parse a pair of floating-point numbers separated by a comma as a complex
+/// number.
+fn parse_complex(s: &str) -> Option<Complex> {
+    parse_pair(s, ',').map(|(re, im)| Complex { re, im })
+}
+
+/// Given the row and column of a pixel in the output image, return the
+/// corresponding point on the complex plane.
+///
+/// `bounds` is a pair giving the width and height of the image in pixels.
+/// `pixel` is a (column, row) pair indicating a particular pixel in that
+/// image. The `upper_left` and `lower_right` parameters are points on the
+/// complex plane designating the area our image covers.
+fn pixel_to_point(bounds: (usize, usize),
+                  pixel: (usize, usize),
+                  upper_left: Complex,
+                  lower_right: Complex)
+    -> Complex
+{
+    // Note that `lower_right.im` is subtracted from `upper_left.im`, not the other
+    // way around. `upper_left.im` is the larger of the two, since row 0 of the
+    // image corresponds to the top of the complex plane, i.e. the largest
+    // imaginary value.
+    let (width, height) = (lower_right.re - upper_left.re,
+                            upper_left.im - lower_right.im);
+    Complex {
+        re: upper_left.re + pixel.0 as f64 * width / bounds.0 as f64,
+        im: upper_left.im - pixel.1 as f64 * height / bounds.1 as f64
+        // Why subtraction here? pixel.1 increases as we go down, but the
+        // imaginary component decreases as we go down.
+    }
+}
+
+/// Render a rectangle of the Mandelbrot set into a buffer of pixels.
+///
+/// The `bounds` argument gives the width and height of the buffer `pixels`,
+/// which holds one grayscale pixel per byte. The `upper_left` and
+/// `lower_right` arguments specify points on the complex plane corresponding
+/// to the upper-left and lower-right corners of the pixel buffer.
+fn render(pixels: &mut [u8],
+          bounds: (usize, usize),
+          upper_left: Complex,
+          lower_right: Complex,
+          limit: u32)
+{
+    assert!(pixels.len() == bounds.0 * bounds.1);
+
+    for row in 0..bounds.1 {
+        for column in 0..bounds.0 {
+            let point = pixel_to_point(bounds, (column, row), upper_left, lower_right);
+            pixels[row * bounds.0 + column] =
+                match escape_time(point, limit) {
+                    // A point that never escapes (as far as we can tell) is
+                    // presumably in the set, so we color it black.
+                    None => 0,
+                    // Darker colors for points that take longer to escape.
+                    Some(count) => 255 - count.min(255) as u8
+                };
+        }
+    }
+}
+
+/// Render a rectangle of the Mandelbrot set into an RGB pixel buffer (three
+/// bytes per pixel), using `escape_time_smooth` and `color` so the image
+/// doesn't show the banding that `render`'s raw iteration counts produce.
+fn render_color(pixels: &mut [u8],
+                 bounds: (usize, usize),
+                 upper_left: Complex,
+                 lower_right: Complex,
+                 limit: u32)
+{
+    assert!(pixels.len() == bounds.0 * bounds.1 * 3);
+
+    for row in 0..bounds.1 {
+        for column in 0..bounds.0 {
+            let point = pixel_to_point(bounds, (column, row), upper_left, lower_right);
+            let pixel = match escape_time_smooth(point, limit) {
+                // As with `render`, members of the set are colored black.
+                None => [0, 0, 0],
+                Some(mu) => color(mu),
+            };
+            let offset = (row * bounds.0 + column) * 3;
+            pixels[offset..offset + 3].copy_from_slice(&pixel);
+        }
+    }
+}
+
+/// The plane region a render call covers, bundled together so the
+/// orchestration functions below (which also need a thread count, a band
+/// size, or both) don't balloon into unreadable argument lists.
+#[derive(Clone, Copy)]
+struct Region {
+    bounds: (usize, usize),
+    upper_left: Complex,
+    lower_right: Complex,
+    limit: u32,
+}
+
+/// A single renderer, grayscale or smooth-colored, that can fill in one
+/// sub-rectangle of an image in one call. `render` and `render_color` both
+/// have this shape, which is what lets `render_bands`/`render_task_queue`
+/// parallelize either one without caring which.
+type RenderFn = fn(&mut [u8], (usize, usize), Complex, Complex, u32);
+
+/// Render a rectangle of the Mandelbrot set, splitting the work into
+/// horizontal bands and rendering the bands concurrently.
+///
+/// `render_one` does the actual per-band rendering (`render` for grayscale,
+/// `render_color` for smooth color); `bytes_per_pixel` must match the pixel
+/// format it expects, e.g. 1 for `render`, 3 for `render_color`. `threads` is
+/// the number of bands (and worker threads) to split the image into. Each
+/// thread renders its own band of `pixels` in place, so no locking is
+/// needed: the bands never overlap.
+fn render_bands(pixels: &mut [u8],
+                 region: Region,
+                 threads: usize,
+                 bytes_per_pixel: usize,
+                 render_one: RenderFn)
+{
+    let Region { bounds, upper_left, lower_right, limit } = region;
+    assert!(pixels.len() == bounds.0 * bounds.1 * bytes_per_pixel);
+    assert!(threads > 0, "render_bands needs at least one thread");
+
+    // Round up, so that the last band picks up any leftover rows rather than
+    // leaving us with more bands than we asked for.
+    let rows_per_band = (bounds.1 / threads + 1).max(1);
+    let bands: Vec<&mut [u8]> =
+        pixels.chunks_mut(rows_per_band * bounds.0 * bytes_per_pixel).collect();
+
+    std::thread::scope(|spawner| {
+        for (i, band) in bands.into_iter().enumerate() {
+            let top = rows_per_band * i;
+            let height = band.len() / (bounds.0 * bytes_per_pixel);
+            let band_bounds = (bounds.0, height);
+            let band_upper_left = pixel_to_point(bounds, (0, top), upper_left, lower_right);
+            let band_lower_right =
+                pixel_to_point(bounds, (bounds.0, top + height), upper_left, lower_right);
+
+            spawner.spawn(move || {
+                render_one(band, band_bounds, band_upper_left, band_lower_right, limit);
+            });
+        }
+    });
+}
+
+/// The number of threads the parallel renderers should use when the caller
+/// has no more specific preference: one per logical CPU.
+fn default_thread_count() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+/// A single horizontal band of the image, along with the row it starts at
+/// (needed to map the band back to its place on the complex plane).
+struct Band<'a> {
+    top: usize,
+    pixels: &'a mut [u8],
+}
+
+/// Render a rectangle of the Mandelbrot set using a shared work queue.
+///
+/// A fixed band-per-thread split (as in `render_bands`) wastes time when some
+/// bands escape quickly and others are deep inside the set: the threads
+/// given the slow bands keep the others idle. Here the image is cut into many
+/// more, smaller bands (`band_rows` rows each) than there are threads, and a
+/// pool of `threads` workers pulls the next unclaimed band from a
+/// mutex-protected queue until none are left, so no thread sits idle while
+/// there is still a band to do. `render_one` and `bytes_per_pixel` mean the
+/// same thing as in `render_bands`.
+fn render_task_queue(pixels: &mut [u8],
+                      region: Region,
+                      threads: usize,
+                      band_rows: usize,
+                      bytes_per_pixel: usize,
+                      render_one: RenderFn)
+{
+    let Region { bounds, upper_left, lower_right, limit } = region;
+    assert!(pixels.len() == bounds.0 * bounds.1 * bytes_per_pixel);
+    assert!(threads > 0, "render_task_queue needs at least one thread");
+
+    let bands: std::collections::VecDeque<Band> = pixels
+        .chunks_mut(band_rows * bounds.0 * bytes_per_pixel)
+        .enumerate()
+        .map(|(i, chunk)| Band { top: band_rows * i, pixels: chunk })
+        .collect();
+    let queue = std::sync::Mutex::new(bands);
+
+    std::thread::scope(|spawner| {
+        for _ in 0..threads {
+            spawner.spawn(|| {
+                loop {
+                    let band = match queue.lock().unwrap().pop_front() {
+                        Some(band) => band,
+                        None => break,
+                    };
+
+                    let height = band.pixels.len() / (bounds.0 * bytes_per_pixel);
+                    let band_bounds = (bounds.0, height);
+                    let band_upper_left =
+                        pixel_to_point(bounds, (0, band.top), upper_left, lower_right);
+                    let band_lower_right = pixel_to_point(
+                        bounds, (bounds.0, band.top + height), upper_left, lower_right);
+
+                    render_one(band.pixels, band_bounds, band_upper_left, band_lower_right, limit);
+                }
+            });
+        }
+    });
+}
+
+/// Which of the renderer's strategies to use for a given image.
+///
+/// `Sequential` is the simplest and the best choice for small images, where
+/// the overhead of spawning threads would outweigh any speedup. `Bands` is
+/// cheap to set up and performs well when escape times are fairly uniform
+/// across the image. `TaskQueue` costs a bit more coordination but balances
+/// load much better when some regions of the set are far more expensive to
+/// compute than others, which is the common case for interesting views.
+#[allow(dead_code)]
+enum RenderStrategy {
+    Sequential,
+    Bands { threads: usize },
+    TaskQueue { threads: usize, band_rows: usize },
+}
+
+/// Render a rectangle of the Mandelbrot set into a grayscale buffer using
+/// the given `strategy`.
+fn render_with_strategy(pixels: &mut [u8], region: Region, strategy: RenderStrategy) {
+    let Region { bounds, upper_left, lower_right, limit } = region;
+    match strategy {
+        RenderStrategy::Sequential =>
+            render(pixels, bounds, upper_left, lower_right, limit),
+        RenderStrategy::Bands { threads } =>
+            render_bands(pixels, region, threads, 1, render),
+        RenderStrategy::TaskQueue { threads, band_rows } =>
+            render_task_queue(pixels, region, threads, band_rows, 1, render),
+    }
+}
+
+/// Render a rectangle of the Mandelbrot set into a smooth-colored RGB buffer
+/// using the given `strategy`. Like `render_with_strategy`, but for
+/// `render_color` instead of `render`.
+fn render_color_with_strategy(pixels: &mut [u8], region: Region, strategy: RenderStrategy) {
+    let Region { bounds, upper_left, lower_right, limit } = region;
+    match strategy {
+        RenderStrategy::Sequential =>
+            render_color(pixels, bounds, upper_left, lower_right, limit),
+        RenderStrategy::Bands { threads } =>
+            render_bands(pixels, region, threads, 3, render_color),
+        RenderStrategy::TaskQueue { threads, band_rows } =>
+            render_task_queue(pixels, region, threads, band_rows, 3, render_color),
+    }
+}
+
+// A CLI front-end, so exploring a different region of the set is a matter of
+// passing different arguments rather than editing and recompiling.
+extern crate clap;
+
+use clap::Parser;
+
+/// Parse a `WIDTHxHEIGHT` argument into a `(width, height)` pixel pair,
+/// wrapping `parse_pair` so clap can report a useful error on bad input.
+///
+/// A width or height of zero parses fine as a `usize`, but produces an
+/// empty pixel buffer (and a zero width panics deep inside the renderer), so
+/// we reject it here instead.
+fn parse_bounds_arg(s: &str) -> Result<(usize, usize), String> {
+    let bounds = parse_pair(s, 'x')
+        .ok_or_else(|| format!("invalid dimensions '{}' (expected WIDTHxHEIGHT)", s))?;
+
+    if bounds.0 == 0 || bounds.1 == 0 {
+        return Err(format!("invalid dimensions '{}' (width and height must be non-zero)", s));
+    }
+
+    Ok(bounds)
+}
+
+/// Parse a `RE,IM` argument into a `Complex`, wrapping `parse_complex` so
+/// clap can report a useful error on bad input.
+fn parse_complex_arg(s: &str) -> Result<Complex, String> {
+    parse_complex(s).ok_or_else(|| format!("invalid point '{}' (expected RE,IM)", s))
+}
+
+/// Render a region of the Mandelbrot set to a PNG file.
+#[derive(Parser)]
+#[command(about = "Render a region of the Mandelbrot set to a PNG file")]
+struct Cli {
+    /// Output PNG filename
+    filename: String,
+
+    /// Image dimensions, as WIDTHxHEIGHT (e.g. 1000x750)
+    #[arg(value_parser = parse_bounds_arg)]
+    bounds: (usize, usize),
+
+    /// Upper-left corner of the plane to render, as RE,IM
+    #[arg(allow_hyphen_values = true, value_parser = parse_complex_arg)]
+    upper_left: Complex,
+
+    /// Lower-right corner of the plane to render, as RE,IM
+    #[arg(allow_hyphen_values = true, value_parser = parse_complex_arg)]
+    lower_right: Complex,
+
+    /// Maximum number of iterations before declaring a point a member
+    #[arg(long, default_value_t = 255)]
+    limit: u32,
+
+    /// Use smooth coloring instead of grayscale
+    #[arg(long)]
+    color: bool,
+}
+
+/// Write the buffer `pixels`, whose dimensions are given by `bounds` and
+/// whose format is given by `color`, to the file named `filename`.
+fn write_image(filename: &str, pixels: &[u8], bounds: (usize, usize), color: ColorType)
+    -> Result<(), std::io::Error>
+{
+    let output = File::create(filename)?;
+
+    let encoder = PNGEncoder::new(output);
+    encoder.encode(pixels, bounds.0 as u32, bounds.1 as u32, color)?;
+
+    Ok(())
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let region = Region {
+        bounds: cli.bounds,
+        upper_left: cli.upper_left,
+        lower_right: cli.lower_right,
+        limit: cli.limit,
+    };
+    let strategy = RenderStrategy::TaskQueue { threads: default_thread_count(), band_rows: 8 };
+
+    if cli.color {
+        // The smooth-colored renderer, parallelized across all available cores.
+        let mut pixels = vec![0; cli.bounds.0 * cli.bounds.1 * 3];
+        render_color_with_strategy(&mut pixels, region, strategy);
+        write_image(&cli.filename, &pixels, cli.bounds, ColorType::RGB(8))
+            .expect("error writing PNG file");
+    } else {
+        // The grayscale renderer, parallelized across all available cores.
+        let mut pixels = vec![0; cli.bounds.0 * cli.bounds.1];
+        render_with_strategy(&mut pixels, region, strategy);
+        write_image(&cli.filename, &pixels, cli.bounds, ColorType::Gray(8))
+            .expect("error writing PNG file");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pixel_to_point_maps_corners() {
+        let bounds = (100, 100);
+        let upper_left = Complex { re: -1.0, im: 1.0 };
+        let lower_right = Complex { re: 1.0, im: -1.0 };
+
+        assert_eq!(
+            pixel_to_point(bounds, (0, 0), upper_left, lower_right),
+            Complex { re: -1.0, im: 1.0 });
+        // Row 0 is the top of the image, which is the *largest* imaginary
+        // value; the last row maps to the smallest.
+        assert_eq!(
+            pixel_to_point(bounds, (100, 100), upper_left, lower_right),
+            Complex { re: 1.0, im: -1.0 });
+        assert_eq!(
+            pixel_to_point(bounds, (50, 50), upper_left, lower_right),
+            Complex { re: 0.0, im: 0.0 });
+    }
+
+    #[test]
+    fn complex_ops_match_complex_arithmetic() {
+        let a = Complex { re: 1.0, im: 2.0 };
+        let b = Complex { re: 3.0, im: -4.0 };
+
+        assert_eq!(a + b, Complex { re: 4.0, im: -2.0 });
+        assert_eq!(a - b, Complex { re: -2.0, im: 6.0 });
+        assert_eq!(a * b, Complex { re: 11.0, im: 2.0 });
+        assert_eq!(-a, Complex { re: -1.0, im: -2.0 });
+        assert_eq!(a.norm_sqr(), 5.0);
+    }
+
+    #[test]
+    fn complex_polar_round_trips() {
+        let c = Complex { re: 3.0, im: 4.0 };
+        let (r, theta) = c.to_polar();
+
+        assert_eq!(r, 5.0);
+        let back = Complex::from_polar(r, theta);
+        assert!((back.re - c.re).abs() < 1e-10);
+        assert!((back.im - c.im).abs() < 1e-10);
+    }
+
+    #[test]
+    fn escape_time_smooth_agrees_with_escape_time() {
+        // 2.0 + 2.0i escapes almost immediately; both functions should say so.
+        let c = Complex { re: 2.0, im: 2.0 };
+        assert_eq!(escape_time(c, 255), Some(0));
+        let mu = escape_time_smooth(c, 255).expect("2.0 + 2.0i should escape");
+        assert!(mu > 0.0 && mu < 1.0);
+
+        // The origin is a member of the set; neither function should escape.
+        let origin = Complex { re: 0.0, im: 0.0 };
+        assert_eq!(escape_time(origin, 255), None);
+        assert_eq!(escape_time_smooth(origin, 255), None);
+    }
+
+    #[test]
+    fn color_is_stable_and_wraps_in_range() {
+        let mu = 12.5;
+        assert_eq!(color(mu), color(mu));
+
+        for &mu in &[0.0, 1.0, 50.0, 1000.0] {
+            for channel in color(mu) {
+                assert!((0..=255).contains(&channel));
+            }
+        }
+    }
+
+    #[test]
+    fn parse_pair_parses_well_formed_input() {
+        assert_eq!(parse_pair::<i32>("",        ','), None);
+        assert_eq!(parse_pair::<i32>("10,",     ','), None);
+        assert_eq!(parse_pair::<i32>(",10",     ','), None);
+        assert_eq!(parse_pair::<i32>("10,20",   ','), Some((10, 20)));
+        assert_eq!(parse_pair::<i32>("10,20xy", ','), None);
+        assert_eq!(parse_pair::<f64>("0.5x",    'x'), None);
+        assert_eq!(parse_pair::<f64>("0.5x1.5", 'x'), Some((0.5, 1.5)));
+    }
+
+    #[test]
+    fn parse_complex_parses_well_formed_input() {
+        assert_eq!(parse_complex("1.25,-0.0625"),
+                   Some(Complex { re: 1.25, im: -0.0625 }));
+        assert_eq!(parse_complex(",-0.0625"), None);
+        assert_eq!(parse_complex("1.25"), None);
+    }
+
+    #[test]
+    fn render_bands_matches_sequential() {
+        // A size that doesn't divide evenly by the thread count below, so
+        // any off-by-one in the band math would show up as missing or
+        // shifted rows rather than getting lucky on a round number.
+        let bounds = (37, 29);
+        let upper_left = Complex { re: -1.5, im: 1.0 };
+        let lower_right = Complex { re: 0.5, im: -1.0 };
+        let limit = 64;
+        let region = Region { bounds, upper_left, lower_right, limit };
+
+        let mut sequential = vec![0u8; bounds.0 * bounds.1];
+        render(&mut sequential, bounds, upper_left, lower_right, limit);
+
+        let mut bands = vec![0u8; bounds.0 * bounds.1];
+        render_bands(&mut bands, region, 5, 1, render);
+        assert_eq!(bands, sequential);
+
+        let mut sequential_color = vec![0u8; bounds.0 * bounds.1 * 3];
+        render_color(&mut sequential_color, bounds, upper_left, lower_right, limit);
+
+        let mut bands_color = vec![0u8; bounds.0 * bounds.1 * 3];
+        render_bands(&mut bands_color, region, 5, 3, render_color);
+        assert_eq!(bands_color, sequential_color);
+    }
+
+    #[test]
+    fn render_task_queue_matches_sequential() {
+        // Same non-round size as the render_bands parity test, with a
+        // band_rows that doesn't divide it evenly either.
+        let bounds = (37, 29);
+        let upper_left = Complex { re: -1.5, im: 1.0 };
+        let lower_right = Complex { re: 0.5, im: -1.0 };
+        let limit = 64;
+        let region = Region { bounds, upper_left, lower_right, limit };
+
+        let mut sequential = vec![0u8; bounds.0 * bounds.1];
+        render(&mut sequential, bounds, upper_left, lower_right, limit);
+
+        let mut task_queue = vec![0u8; bounds.0 * bounds.1];
+        render_task_queue(&mut task_queue, region, 5, 3, 1, render);
+        assert_eq!(task_queue, sequential);
+
+        let mut sequential_color = vec![0u8; bounds.0 * bounds.1 * 3];
+        render_color(&mut sequential_color, bounds, upper_left, lower_right, limit);
+
+        let mut task_queue_color = vec![0u8; bounds.0 * bounds.1 * 3];
+        render_task_queue(&mut task_queue_color, region, 5, 3, 3, render_color);
+        assert_eq!(task_queue_color, sequential_color);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one thread")]
+    fn render_bands_rejects_zero_threads() {
+        let region = Region {
+            bounds: (4, 4),
+            upper_left: Complex { re: -1.0, im: 1.0 },
+            lower_right: Complex { re: 1.0, im: -1.0 },
+            limit: 16,
+        };
+        let mut pixels = vec![0u8; 16];
+        render_bands(&mut pixels, region, 0, 1, render);
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one thread")]
+    fn render_task_queue_rejects_zero_threads() {
+        let region = Region {
+            bounds: (4, 4),
+            upper_left: Complex { re: -1.0, im: 1.0 },
+            lower_right: Complex { re: 1.0, im: -1.0 },
+            limit: 16,
+        };
+        let mut pixels = vec![0u8; 16];
+        render_task_queue(&mut pixels, region, 0, 2, 1, render);
+    }
 }
\ No newline at end of file